@@ -1,3 +1,12 @@
+mod allow;
+mod guard;
+mod scope;
+
+use allow::{method_not_allowed_response, AllowedMethodsTable};
+pub use allow::TrackedRouter;
+pub use guard::{All, Always, Any, Guard, Header, Host, Not};
+pub use scope::Scope;
+
 use crate::{
     body::{Body, HttpBody},
     response::{Redirect, Response},
@@ -11,62 +20,40 @@ use http::Request;
 use matchit::MatchError;
 use std::{
     convert::Infallible,
-    ops::Deref,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
     task::{Context, Poll},
 };
+use tokio::sync::watch;
 use tower::Service;
 
-/// A [`Route Service`] that can be reloaded at runtime.
+/// The route-matching core of a [`ReloadableRouterService`]: looks up the
+/// current [`Router`] snapshot and dispatches to it.
+///
+/// Split out so a [`ReloadableRouterService`] can wrap it in a fixed outer
+/// [`tower::Layer`] chain (see [`ReloadableRouterService::gate`]) that
+/// survives router reloads, since only this inner service's `Arc<ArcSwap<_>>`
+/// is swapped out on reload.
 #[derive(Debug)]
-pub struct ReloadableRouterService<B = Body> {
-    inner: Arc<ArcSwap<Router<B>>>,
+pub struct RouterDispatch<B = Body> {
+    router: Arc<ArcSwap<Router<B>>>,
+    allowed_methods: Arc<ArcSwap<AllowedMethodsTable>>,
 }
 
-/// TODO: This is unsafe!!!! just to make it compile
-unsafe impl<B> Send for ReloadableRouterService<B> {}
-
-impl<B> Deref for ReloadableRouterService<B> {
-    type Target = Arc<ArcSwap<Router<B>>>;
-
-    fn deref(&self) -> &Self::Target {
-        &self.inner
-    }
-}
-
-impl<B> Clone for ReloadableRouterService<B> {
+impl<B> Clone for RouterDispatch<B> {
     fn clone(&self) -> Self {
         Self {
-            inner: self.inner.clone(),
+            router: self.router.clone(),
+            allowed_methods: self.allowed_methods.clone(),
         }
     }
 }
 
-impl<B> Default for ReloadableRouterService<B>
+impl<B> Service<Request<B>> for RouterDispatch<B>
 where
-    B: HttpBody + Send + 'static,
-{
-    fn default() -> Self {
-        Self {
-            inner: Arc::new(ArcSwap::from_pointee(Router::default())),
-        }
-    }
-}
-
-impl<B> From<Router<B>> for ReloadableRouterService<B>
-where
-    B: HttpBody + Send + 'static,
-{
-    fn from(svc: Router<B>) -> Self {
-        Self {
-            inner: Arc::new(ArcSwap::from_pointee(svc)),
-        }
-    }
-}
-
-impl<B> Service<Request<B>> for ReloadableRouterService<B>
-where
-    B: HttpBody + Send + 'static,
+    B: HttpBody + Send + Sync + 'static,
 {
     type Response = Response;
     type Error = Infallible;
@@ -90,10 +77,17 @@ where
         }
 
         let path = req.uri().path().to_owned();
-        let this = self.load_full();
+        let this = self.router.load_full();
 
         match this.node.at(&path) {
-            Ok(match_) => this.call_route(match_, req),
+            Ok(match_) => {
+                if let Some(methods) = self.allowed_methods.load().get(&path) {
+                    if !methods.contains(req.method()) {
+                        return RouteFuture::from_response(method_not_allowed_response(methods));
+                    }
+                }
+                this.call_route(match_, req)
+            }
             Err(err) => {
                 let mut fallback = match &this.fallback {
                     Fallback::Default(inner) => inner.clone(),
@@ -122,6 +116,213 @@ where
     }
 }
 
+/// A [`Route Service`] that can be reloaded at runtime.
+///
+/// Reloads are performed through a [`ReloadHandle`] obtained via
+/// [`ReloadableRouterService::handle`]. In-flight requests keep running
+/// against the [`Router`] snapshot they captured; only requests that start
+/// after a reload see the new route table.
+///
+/// `S` is the service that actually handles requests: by default it's the
+/// bare [`RouterDispatch`], but [`ReloadableRouterService::gate`] can wrap it
+/// in a `tower::Layer` chain (auth, logging, CORS, ...) that stays in place
+/// across reloads, since only the `Arc<ArcSwap<Router<B>>>` underneath it is
+/// ever swapped out.
+#[derive(Debug)]
+pub struct ReloadableRouterService<B = Body, S = RouterDispatch<B>> {
+    router: Arc<ArcSwap<Router<B>>>,
+    allowed_methods: Arc<ArcSwap<AllowedMethodsTable>>,
+    generation: Arc<AtomicU64>,
+    reload_tx: watch::Sender<u64>,
+    gate: S,
+}
+
+/// A handle used to reload the [`Router`] backing a [`ReloadableRouterService`]
+/// at runtime, and to subscribe to reload notifications.
+///
+/// Obtained from [`ReloadableRouterService::handle`]. Cloning a handle is
+/// cheap; all clones reload the same underlying service.
+#[derive(Debug)]
+pub struct ReloadHandle<B = Body> {
+    router: Arc<ArcSwap<Router<B>>>,
+    allowed_methods: Arc<ArcSwap<AllowedMethodsTable>>,
+    generation: Arc<AtomicU64>,
+    reload_tx: watch::Sender<u64>,
+}
+
+impl<B> Clone for ReloadHandle<B> {
+    fn clone(&self) -> Self {
+        Self {
+            router: self.router.clone(),
+            allowed_methods: self.allowed_methods.clone(),
+            generation: self.generation.clone(),
+            reload_tx: self.reload_tx.clone(),
+        }
+    }
+}
+
+impl<B> ReloadHandle<B> {
+    /// Atomically replace the router with `router`. A bare [`Router`] carries
+    /// no [`TrackedRouter`] method table, so this also clears any per-path
+    /// allowed-methods tracking a prior [`reload_tracked`](Self::reload_tracked)
+    /// put in place; use `reload_tracked` to replace both together.
+    pub fn reload(&self, router: Router<B>) {
+        self.router.store(Arc::new(router));
+        self.allowed_methods.store(Arc::new(AllowedMethodsTable::new()));
+        self.notify_reloaded();
+    }
+
+    /// Atomically replace the router using a read-copy-update closure over
+    /// the current router, via [`ArcSwap::rcu`]. The closure may be invoked
+    /// more than once if another writer races it, matching `rcu`'s contract.
+    ///
+    /// Like [`reload`](Self::reload), this clears any allowed-methods
+    /// tracking from a prior [`reload_tracked`](Self::reload_tracked).
+    pub fn reload_with(&self, mut f: impl FnMut(&Router<B>) -> Router<B>) {
+        self.router.rcu(|current| Arc::new(f(current)));
+        self.allowed_methods.store(Arc::new(AllowedMethodsTable::new()));
+        self.notify_reloaded();
+    }
+
+    /// Atomically replace both the router and its per-path allowed-methods
+    /// table built by [`TrackedRouter::route_allow`], so a reload doesn't
+    /// lose 405 tracking the way [`reload`](Self::reload) does for a bare
+    /// [`Router`].
+    pub fn reload_tracked(&self, tracked: TrackedRouter<B>) {
+        let (router, allowed_methods) = tracked.into_parts();
+        self.router.store(Arc::new(router));
+        self.allowed_methods.store(Arc::new(allowed_methods));
+        self.notify_reloaded();
+    }
+
+    /// Subscribe to reload notifications. The receiver yields a
+    /// monotonically increasing generation number each time the router is
+    /// swapped.
+    pub fn on_reload(&self) -> watch::Receiver<u64> {
+        self.reload_tx.subscribe()
+    }
+
+    /// The generation number of the router currently in effect.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    fn notify_reloaded(&self) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        // No receivers is not an error: nobody has to be listening for reloads.
+        let _ = self.reload_tx.send(generation);
+    }
+}
+
+impl<B, S> ReloadableRouterService<B, S> {
+    /// Get a [`ReloadHandle`] that can be used to reload this service's
+    /// router from anywhere, including after the service itself has been
+    /// handed off to a server.
+    pub fn handle(&self) -> ReloadHandle<B> {
+        ReloadHandle {
+            router: self.router.clone(),
+            allowed_methods: self.allowed_methods.clone(),
+            generation: self.generation.clone(),
+            reload_tx: self.reload_tx.clone(),
+        }
+    }
+
+    /// Apply `layer` as a fixed outer gate around the current gate chain
+    /// (and, underneath it, every route and the fallback), such as auth,
+    /// logging, or CORS. Unlike wrapping the whole service from the
+    /// outside, the gate is stored *inside* `ReloadableRouterService`, so a
+    /// later [`ReloadHandle::reload`] only replaces the route-matching core
+    /// underneath it and never drops the gate. Calling `gate` more than once
+    /// stacks each layer outside the previous ones, the same way repeated
+    /// calls to [`Router::layer`] compose.
+    pub fn gate<L>(self, layer: L) -> ReloadableRouterService<B, L::Service>
+    where
+        L: tower::Layer<S>,
+    {
+        ReloadableRouterService {
+            gate: layer.layer(self.gate),
+            router: self.router,
+            allowed_methods: self.allowed_methods,
+            generation: self.generation,
+            reload_tx: self.reload_tx,
+        }
+    }
+}
+
+impl<B, S> Clone for ReloadableRouterService<B, S>
+where
+    S: Clone,
+{
+    fn clone(&self) -> Self {
+        Self {
+            router: self.router.clone(),
+            allowed_methods: self.allowed_methods.clone(),
+            generation: self.generation.clone(),
+            reload_tx: self.reload_tx.clone(),
+            gate: self.gate.clone(),
+        }
+    }
+}
+
+impl<B> Default for ReloadableRouterService<B>
+where
+    B: HttpBody + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::from(TrackedRouter::default())
+    }
+}
+
+impl<B> From<Router<B>> for ReloadableRouterService<B>
+where
+    B: HttpBody + Send + Sync + 'static,
+{
+    fn from(router: Router<B>) -> Self {
+        Self::from(TrackedRouter::new(router))
+    }
+}
+
+impl<B> From<TrackedRouter<B>> for ReloadableRouterService<B>
+where
+    B: HttpBody + Send + Sync + 'static,
+{
+    fn from(tracked: TrackedRouter<B>) -> Self {
+        let (router, allowed_methods) = tracked.into_parts();
+        let (reload_tx, _) = watch::channel(0);
+        let router = Arc::new(ArcSwap::from_pointee(router));
+        let allowed_methods = Arc::new(ArcSwap::from_pointee(allowed_methods));
+        Self {
+            gate: RouterDispatch {
+                router: router.clone(),
+                allowed_methods: allowed_methods.clone(),
+            },
+            router,
+            allowed_methods,
+            generation: Arc::new(AtomicU64::new(0)),
+            reload_tx,
+        }
+    }
+}
+
+impl<B, S> Service<Request<B>> for ReloadableRouterService<B, S>
+where
+    S: Service<Request<B>, Response = Response, Error = Infallible>,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = S::Future;
+
+    #[inline]
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.gate.poll_ready(cx)
+    }
+
+    #[inline]
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        self.gate.call(req)
+    }
+}
+
 #[cfg(test)]
 mod tests {
 
@@ -171,11 +372,70 @@ mod tests {
             get(|_: Request<Body>| async { "users#index" })
                 .post(|_: Request<Body>| async { "users#new" }),
         );
-        app.store(Arc::new(new_router));
+
+        let handle = app.handle();
+        let mut on_reload = handle.on_reload();
+        handle.reload(new_router);
+
+        on_reload.changed().await.unwrap();
+        assert_eq!(*on_reload.borrow(), 1);
+
         let res = client.post("/users").send().await;
         assert_eq!(res.status(), StatusCode::OK);
         assert_eq!(res.text().await, "users#new");
         let res = client.get("/users/1/action").send().await;
         assert_eq!(res.status(), StatusCode::NOT_FOUND);
     }
+
+    #[tokio::test]
+    async fn gate_survives_reload() {
+        use tower_http::set_header::SetResponseHeaderLayer;
+
+        let app: ReloadableRouterService = Router::new()
+            .route("/users", get(|_: Request<Body>| async { "users#index" }))
+            .into();
+
+        let app = app.gate(SetResponseHeaderLayer::overriding(
+            http::header::SERVER,
+            http::HeaderValue::from_static("axum"),
+        ));
+
+        let client = TestClient::new(app.clone());
+
+        let res = client.get("/users").send().await;
+        assert_eq!(res.headers()[http::header::SERVER], "axum");
+
+        app.handle().reload(
+            Router::new().route("/users", get(|_: Request<Body>| async { "users#new" })),
+        );
+
+        let res = client.get("/users").send().await;
+        assert_eq!(res.text().await, "users#new");
+        assert_eq!(res.headers()[http::header::SERVER], "axum");
+    }
+
+    #[tokio::test]
+    async fn repeated_gate_calls_stack_instead_of_replacing() {
+        use tower_http::set_header::SetResponseHeaderLayer;
+
+        let app: ReloadableRouterService = Router::new()
+            .route("/users", get(|_: Request<Body>| async { "users#index" }))
+            .into();
+
+        let app = app
+            .gate(SetResponseHeaderLayer::overriding(
+                http::header::SERVER,
+                http::HeaderValue::from_static("axum"),
+            ))
+            .gate(SetResponseHeaderLayer::overriding(
+                http::HeaderName::from_static("x-gate"),
+                http::HeaderValue::from_static("second"),
+            ));
+
+        let client = TestClient::new(app);
+
+        let res = client.get("/users").send().await;
+        assert_eq!(res.headers()[http::header::SERVER], "axum");
+        assert_eq!(res.headers()["x-gate"], "second");
+    }
 }
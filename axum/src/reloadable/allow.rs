@@ -0,0 +1,184 @@
+use crate::{body::HttpBody, routing::MethodRouter, Router};
+use axum_core::response::IntoResponse;
+use http::{HeaderValue, Method, StatusCode};
+use std::{collections::HashMap, sync::Arc};
+
+/// Per-path table of the HTTP methods explicitly registered via
+/// [`TrackedRouter::route_allow`].
+///
+/// Consulted live by [`RouterDispatch::call`](super::RouterDispatch), which
+/// answers a request for an unregistered method at a tracked path with a
+/// real `405 Method Not Allowed` and a correct `Allow` header, short-
+/// circuiting before the path's own route (or the router's fallback) ever
+/// sees the request.
+///
+/// Keyed by the exact literal path passed to `route_allow`, matched against
+/// the exact request path: a path carrying `matchit` parameters (e.g.
+/// `/users/:id`) is only ever looked up by its registered literal form, so
+/// this table doesn't help parameterized routes. Doing that in general
+/// needs the matched route *pattern* back out of the router's internal
+/// node, which isn't exposed to this crate.
+pub(crate) type AllowedMethodsTable = HashMap<String, Arc<[Method]>>;
+
+/// Build the `405 Method Not Allowed` response for a tracked path's allowed
+/// methods.
+pub(crate) fn method_not_allowed_response(methods: &[Method]) -> crate::response::Response {
+    let allow_header = HeaderValue::from_str(
+        &methods
+            .iter()
+            .map(Method::as_str)
+            .collect::<Vec<_>>()
+            .join(", "),
+    )
+    .expect("HTTP method names are always valid header values");
+
+    let mut response = StatusCode::METHOD_NOT_ALLOWED.into_response();
+    response
+        .headers_mut()
+        .insert(http::header::ALLOW, allow_header);
+    response
+}
+
+/// A [`Router`] paired with the set of methods registered at each of its
+/// paths via [`TrackedRouter::route_allow`], so that
+/// [`ReloadableRouterService`](crate::reloadable::ReloadableRouterService)
+/// can answer an unregistered method with a real `405 Method Not Allowed`
+/// and a correct `Allow` header instead of whatever the route (or the
+/// router's fallback) would otherwise do.
+///
+/// Build one with [`TrackedRouter::new`] (a bare `Router` also converts via
+/// `.into()`, tracking nothing), then hand it to
+/// `ReloadableRouterService::from` or
+/// [`ReloadHandle::reload_tracked`](crate::reloadable::ReloadHandle::reload_tracked).
+pub struct TrackedRouter<B> {
+    router: Router<B>,
+    allowed_methods: AllowedMethodsTable,
+}
+
+impl<B> TrackedRouter<B>
+where
+    B: HttpBody + Send + Sync + 'static,
+{
+    /// Wrap an existing [`Router`], tracking no methods yet.
+    pub fn new(router: Router<B>) -> Self {
+        Self {
+            router,
+            allowed_methods: AllowedMethodsTable::new(),
+        }
+    }
+
+    /// Register `method_router` at `path`, exactly like [`Router::route`],
+    /// and record `methods` as the full set of methods allowed there. A
+    /// request for any other method at `path` gets a real `405` with a
+    /// correct `Allow` header instead of whatever `method_router` does for
+    /// a method it has no handler for.
+    ///
+    /// `methods` is independent of how `method_router` itself was built
+    /// (e.g. `get(h1).post(h2)`), so splitting handlers per method works
+    /// exactly as it would with a plain [`Router::route`] call.
+    pub fn route_allow(
+        mut self,
+        path: &str,
+        methods: impl IntoIterator<Item = Method>,
+        method_router: MethodRouter<B>,
+    ) -> Self {
+        self.allowed_methods
+            .insert(path.to_owned(), methods.into_iter().collect());
+        self.router = self.router.route(path, method_router);
+        self
+    }
+
+    /// Register a route the normal way, with no per-method 405 tracking.
+    pub fn route(mut self, path: &str, method_router: MethodRouter<B>) -> Self {
+        self.router = self.router.route(path, method_router);
+        self
+    }
+
+    pub(crate) fn into_parts(self) -> (Router<B>, AllowedMethodsTable) {
+        (self.router, self.allowed_methods)
+    }
+}
+
+impl<B> Default for TrackedRouter<B>
+where
+    B: HttpBody + Send + Sync + 'static,
+{
+    fn default() -> Self {
+        Self::new(Router::default())
+    }
+}
+
+impl<B> From<Router<B>> for TrackedRouter<B>
+where
+    B: HttpBody + Send + Sync + 'static,
+{
+    fn from(router: Router<B>) -> Self {
+        Self::new(router)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        body::Body, reloadable::ReloadableRouterService, routing::get, test_helpers::TestClient,
+    };
+    use http::{header::ALLOW, Request};
+
+    async fn index(_: Request<Body>) -> &'static str {
+        "users#index"
+    }
+
+    async fn create(_: Request<Body>) -> &'static str {
+        "users#create"
+    }
+
+    #[tokio::test]
+    async fn allowed_method_is_dispatched() {
+        let app: ReloadableRouterService = TrackedRouter::new(Router::new())
+            .route_allow(
+                "/users",
+                [Method::GET, Method::POST],
+                get(index).post(create),
+            )
+            .into();
+
+        let client = TestClient::new(app);
+
+        let res = client.get("/users").send().await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "users#index");
+
+        let res = client.post("/users").send().await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "users#create");
+    }
+
+    #[tokio::test]
+    async fn disallowed_method_gets_405_with_allow_header() {
+        let app: ReloadableRouterService = TrackedRouter::new(Router::new())
+            .route_allow(
+                "/users",
+                [Method::GET, Method::POST],
+                get(index).post(create),
+            )
+            .into();
+
+        let client = TestClient::new(app);
+
+        let res = client.delete("/users").send().await;
+        assert_eq!(res.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(res.headers()[ALLOW], "GET, POST");
+    }
+
+    #[tokio::test]
+    async fn untracked_routes_are_unaffected() {
+        let app: ReloadableRouterService =
+            TrackedRouter::new(Router::new().route("/plain", get(index))).into();
+
+        let client = TestClient::new(app);
+
+        let res = client.get("/plain").send().await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,408 @@
+use crate::{body::HttpBody, routing::Fallback, routing::MethodRouter, Router};
+use http::{request::Parts, Request};
+use std::{
+    sync::Arc,
+    task::{Context, Poll},
+};
+use tower::{util::BoxCloneService, Layer, Service};
+
+/// A predicate that decides whether an endpoint should handle a given request.
+///
+/// Guards let a single path dispatch to different handlers depending on
+/// properties of the request (headers, host, scheme, or custom logic),
+/// similar to actix-web's `guard::Guard`.
+pub trait Guard: Send + Sync + 'static {
+    /// Returns `true` if the request satisfies this guard.
+    fn check(&self, parts: &Parts) -> bool;
+}
+
+impl Guard for Box<dyn Guard> {
+    fn check(&self, parts: &Parts) -> bool {
+        (**self).check(parts)
+    }
+}
+
+/// Matches requests that carry a header equal to the given value.
+#[derive(Debug, Clone)]
+pub struct Header {
+    name: http::header::HeaderName,
+    value: http::header::HeaderValue,
+}
+
+impl Header {
+    /// Create a guard matching a single header name/value pair.
+    pub fn new(name: http::header::HeaderName, value: http::header::HeaderValue) -> Self {
+        Self { name, value }
+    }
+}
+
+impl Guard for Header {
+    fn check(&self, parts: &Parts) -> bool {
+        parts.headers.get(&self.name) == Some(&self.value)
+    }
+}
+
+/// Matches requests whose `Host` header equals the given value.
+#[derive(Debug, Clone)]
+pub struct Host(pub String);
+
+impl Guard for Host {
+    fn check(&self, parts: &Parts) -> bool {
+        parts
+            .headers
+            .get(http::header::HOST)
+            .and_then(|value| value.to_str().ok())
+            == Some(self.0.as_str())
+    }
+}
+
+/// Matches every request, regardless of any other guard.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Always;
+
+impl Guard for Always {
+    fn check(&self, _parts: &Parts) -> bool {
+        true
+    }
+}
+
+/// Matches only if every guard in the set matches (logical AND).
+#[derive(Default)]
+pub struct All(pub Vec<Box<dyn Guard>>);
+
+impl Guard for All {
+    fn check(&self, parts: &Parts) -> bool {
+        self.0.iter().all(|guard| guard.check(parts))
+    }
+}
+
+/// Matches if at least one guard in the set matches (logical OR).
+#[derive(Default)]
+pub struct Any(pub Vec<Box<dyn Guard>>);
+
+impl Guard for Any {
+    fn check(&self, parts: &Parts) -> bool {
+        self.0.iter().any(|guard| guard.check(parts))
+    }
+}
+
+/// Inverts the result of an inner guard.
+pub struct Not<G>(pub G);
+
+impl<G> Guard for Not<G>
+where
+    G: Guard,
+{
+    fn check(&self, parts: &Parts) -> bool {
+        !self.0.check(parts)
+    }
+}
+
+type BoxFallback<B> =
+    BoxCloneService<Request<B>, crate::response::Response, std::convert::Infallible>;
+
+fn clone_fallback<B>(router: &Router<B>) -> BoxFallback<B>
+where
+    B: Send + 'static,
+{
+    match &router.fallback {
+        Fallback::Default(inner) => BoxCloneService::new(inner.clone()),
+        Fallback::Custom(inner) => BoxCloneService::new(inner.clone()),
+    }
+}
+
+/// One guarded candidate registered at a shared path: `service` only handles
+/// a request once every guard in the set has matched.
+type Candidate<S> = (Arc<Vec<Box<dyn Guard>>>, S);
+
+/// Dispatches a request to the first of several candidate services whose
+/// guards all pass, falling through to a captured fallback service
+/// (the router's or scope's own fallback, not a hardcoded 404) when none do.
+///
+/// This is how "guards stored alongside each endpoint" is realized for a
+/// single path: `matchit` only ever resolves one node per path, so every
+/// guarded alternative for that path is collected into one
+/// `GuardedService` up front and selected between at request time.
+#[derive(Clone)]
+pub(crate) struct GuardedService<S, B> {
+    candidates: Arc<Vec<Candidate<S>>>,
+    fallback: BoxFallback<B>,
+}
+
+/// A [`tower::Layer`] that ANDs a fixed set of guards onto whatever service
+/// it wraps, falling back to `fallback` when the guards reject a request.
+/// Used to apply a scope's guards to every route nested under it.
+#[derive(Clone)]
+pub(crate) struct GuardLayer<B> {
+    guards: Arc<Vec<Box<dyn Guard>>>,
+    fallback: BoxFallback<B>,
+}
+
+impl<B> GuardLayer<B> {
+    pub(crate) fn new(guards: Vec<Box<dyn Guard>>, fallback: BoxFallback<B>) -> Self {
+        Self {
+            guards: Arc::new(guards),
+            fallback,
+        }
+    }
+}
+
+impl<S, B> Layer<S> for GuardLayer<B> {
+    type Service = GuardedService<S, B>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        GuardedService {
+            candidates: Arc::new(vec![(self.guards.clone(), inner)]),
+            fallback: self.fallback.clone(),
+        }
+    }
+}
+
+impl<S, B> Service<Request<B>> for GuardedService<S, B>
+where
+    S: Service<Request<B>, Response = crate::response::Response, Error = std::convert::Infallible>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send + 'static,
+    B: Send + 'static,
+{
+    type Response = crate::response::Response;
+    type Error = std::convert::Infallible;
+    type Future = std::pin::Pin<
+        Box<dyn std::future::Future<Output = Result<Self::Response, Self::Error>> + Send>,
+    >;
+
+    fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        // Candidates and the fallback are only known to be ready once called;
+        // `Service::call` on a cloned, concrete tower service is cheap.
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, req: Request<B>) -> Self::Future {
+        let (parts, body) = req.into_parts();
+        let matched = self
+            .candidates
+            .iter()
+            .find(|(guards, _)| guards.iter().all(|guard| guard.check(&parts)));
+        let req = Request::from_parts(parts, body);
+
+        match matched {
+            Some((_, service)) => {
+                let mut service = service.clone();
+                Box::pin(async move { service.call(req).await })
+            }
+            None => {
+                let mut fallback = self.fallback.clone();
+                Box::pin(async move { fallback.call(req).await })
+            }
+        }
+    }
+}
+
+impl<B> Router<B>
+where
+    B: HttpBody + Send + Sync + 'static,
+{
+    /// Register several guarded alternatives at the same `path`: for each
+    /// request matching `path`, the first `(guards, method_router)` entry
+    /// whose guards all [`Guard::check`] is dispatched to. If none match,
+    /// the request falls through to the router's fallback exactly as if
+    /// `path` had not matched at all, the way "no such path" normally does.
+    ///
+    /// # Ordering
+    ///
+    /// The fallback used for guard-rejected requests is whichever one is in
+    /// effect on `self` *at the moment `route_with_guards` is called* — it
+    /// is captured once, here, not read back from the router at request
+    /// time. Call [`Router::fallback`] before `route_with_guards`, not
+    /// after: a `.fallback()` call made afterwards still applies to every
+    /// other unmatched path, but not to guard rejections at `path`, which
+    /// keep using whatever fallback was in effect when `route_with_guards`
+    /// ran.
+    pub fn route_with_guards(
+        self,
+        path: &str,
+        candidates: Vec<(Vec<Box<dyn Guard>>, MethodRouter<B>)>,
+    ) -> Self {
+        let fallback = clone_fallback(&self);
+        let candidates = candidates
+            .into_iter()
+            .map(|(guards, method_router)| (Arc::new(guards), method_router))
+            .collect();
+        let guarded = GuardedService {
+            candidates: Arc::new(candidates),
+            fallback,
+        };
+        self.route_service(path, guarded)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use http::Request;
+
+    fn parts_with_header(name: http::header::HeaderName, value: &str) -> Parts {
+        let (parts, _) = Request::builder()
+            .header(name, value)
+            .body(())
+            .unwrap()
+            .into_parts();
+        parts
+    }
+
+    #[test]
+    fn header_guard_matches_exact_value() {
+        let guard = Header::new(http::header::ACCEPT, http::HeaderValue::from_static("json"));
+        let parts = parts_with_header(http::header::ACCEPT, "json");
+        assert!(guard.check(&parts));
+
+        let parts = parts_with_header(http::header::ACCEPT, "xml");
+        assert!(!guard.check(&parts));
+    }
+
+    #[test]
+    fn host_guard_matches_host_header() {
+        let guard = Host("example.com".to_owned());
+        let parts = parts_with_header(http::header::HOST, "example.com");
+        assert!(guard.check(&parts));
+
+        let parts = parts_with_header(http::header::HOST, "other.com");
+        assert!(!guard.check(&parts));
+    }
+
+    #[test]
+    fn combinators_compose() {
+        let parts = parts_with_header(http::header::HOST, "example.com");
+
+        let all = All(vec![
+            Box::new(Always),
+            Box::new(Host("example.com".to_owned())),
+        ]);
+        assert!(all.check(&parts));
+
+        let not_always = Not(Always);
+        assert!(!not_always.check(&parts));
+
+        let any = Any(vec![
+            Box::new(Host("other.com".to_owned())),
+            Box::new(Host("example.com".to_owned())),
+        ]);
+        assert!(any.check(&parts));
+    }
+
+    #[tokio::test]
+    async fn guarded_service_dispatches_first_match_else_falls_through() {
+        use axum_core::response::IntoResponse;
+        use http::StatusCode;
+        use tower::service_fn;
+
+        async fn a(_: Request<()>) -> Result<crate::response::Response, std::convert::Infallible> {
+            Ok("a".into_response())
+        }
+
+        async fn b(_: Request<()>) -> Result<crate::response::Response, std::convert::Infallible> {
+            Ok("b".into_response())
+        }
+
+        async fn fallback(
+            _: Request<()>,
+        ) -> Result<crate::response::Response, std::convert::Infallible> {
+            Ok(StatusCode::NOT_FOUND.into_response())
+        }
+
+        let mut guarded = GuardedService {
+            candidates: Arc::new(vec![
+                (
+                    Arc::new(vec![Box::new(Host("other.com".to_owned())) as Box<dyn Guard>]),
+                    BoxCloneService::new(service_fn(a)),
+                ),
+                (
+                    Arc::new(vec![Box::new(Host("example.com".to_owned())) as Box<dyn Guard>]),
+                    BoxCloneService::new(service_fn(b)),
+                ),
+            ]),
+            fallback: BoxCloneService::new(service_fn(fallback)),
+        };
+
+        let req = Request::builder()
+            .header(http::header::HOST, "example.com")
+            .body(())
+            .unwrap();
+        let res = guarded.call(req).await.unwrap();
+        assert_eq!(
+            hyper::body::to_bytes(res.into_body()).await.unwrap(),
+            "b".as_bytes()
+        );
+
+        let req = Request::builder()
+            .header(http::header::HOST, "nowhere.com")
+            .body(())
+            .unwrap();
+        let res = guarded.call(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn route_with_guards_uses_the_fallback_set_before_it_was_called() {
+        use crate::{reloadable::ReloadableRouterService, routing::get, test_helpers::TestClient};
+        use http::StatusCode;
+        use hyper::Body;
+
+        let app: ReloadableRouterService = Router::new()
+            .fallback(get(|_: Request<Body>| async {
+                (StatusCode::IM_A_TEAPOT, "custom fallback")
+            }))
+            .route_with_guards(
+                "/users",
+                vec![(
+                    vec![Box::new(Host("example.com".to_owned())) as Box<dyn Guard>],
+                    get(|_: Request<Body>| async { "users#index" }),
+                )],
+            )
+            .into();
+
+        let client = TestClient::new(app);
+        let res = client
+            .get("/users")
+            .header(http::header::HOST, "other.com")
+            .send()
+            .await;
+        assert_eq!(res.status(), StatusCode::IM_A_TEAPOT);
+        assert_eq!(res.text().await, "custom fallback");
+    }
+
+    #[tokio::test]
+    async fn route_with_guards_ignores_a_fallback_set_after_it_was_called() {
+        use crate::{reloadable::ReloadableRouterService, routing::get, test_helpers::TestClient};
+        use http::StatusCode;
+        use hyper::Body;
+
+        // Documented limitation (see `Router::route_with_guards`'s doc
+        // comment): the fallback used for guard rejections is captured at
+        // the moment `route_with_guards` runs, so a later `.fallback()`
+        // call has no effect on it even though it does apply to every other
+        // unmatched path.
+        let app: ReloadableRouterService = Router::new()
+            .route_with_guards(
+                "/users",
+                vec![(
+                    vec![Box::new(Host("example.com".to_owned())) as Box<dyn Guard>],
+                    get(|_: Request<Body>| async { "users#index" }),
+                )],
+            )
+            .fallback(get(|_: Request<Body>| async {
+                (StatusCode::IM_A_TEAPOT, "custom fallback")
+            }))
+            .into();
+
+        let client = TestClient::new(app);
+        let res = client
+            .get("/users")
+            .header(http::header::HOST, "other.com")
+            .send()
+            .await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -0,0 +1,179 @@
+use super::{guard::GuardLayer, Guard};
+use crate::{body::HttpBody, routing::Fallback, routing::MethodRouter, Router};
+use tower::Layer;
+
+/// A group of routes sharing a common path prefix, fallback, and set of
+/// guards, built with [`Router::scope`].
+///
+/// Ported from actix-web's `scope`: every route added inside the closure
+/// passed to [`Router::scope`] is nested under the shared prefix, and every
+/// guard added with [`Scope::guard`] is ANDed onto all of them.
+pub struct Scope<B> {
+    router: Router<B>,
+    guards: Vec<Box<dyn Guard>>,
+    fallback: Option<MethodRouter<B>>,
+}
+
+/// The catch-all path a scope's own fallback is registered under.
+///
+/// `Router::nest` merges a nested router's routes into the outer router's
+/// node, but does not carry over the nested router's own `fallback` field —
+/// an unmatched path under the prefix falls through to whatever fallback is
+/// set on the *outer* router, not the nested one. So `Scope::fallback` can't
+/// delegate to `Router::fallback` the way `Scope::route` delegates to
+/// `Router::route`; it needs its own reachable entry in the scope's route
+/// table, registered as a low-priority catch-all, to survive nesting at all.
+const SCOPE_FALLBACK_PATH: &str = "/*__scope_fallback";
+
+impl<B> Scope<B>
+where
+    B: HttpBody + Send + 'static,
+{
+    fn new() -> Self {
+        Self {
+            router: Router::new(),
+            guards: Vec::new(),
+            fallback: None,
+        }
+    }
+
+    /// Add a route under this scope's prefix.
+    pub fn route(mut self, path: &str, method_router: MethodRouter<B>) -> Self {
+        self.router = self.router.route(path, method_router);
+        self
+    }
+
+    /// Set the fallback used when a request matches this scope's prefix but
+    /// no route inside it. This can't simply delegate to `Router::fallback`
+    /// under the hood — see the comment on `SCOPE_FALLBACK_PATH` for why.
+    pub fn fallback(mut self, method_router: MethodRouter<B>) -> Self {
+        self.fallback = Some(method_router);
+        self
+    }
+
+    /// Add a guard that every route in this scope must additionally satisfy.
+    /// A request the scope's guards reject is handed to the scope's
+    /// [`fallback`](Self::fallback) if one is set, and otherwise to the
+    /// scope's default `404 Not Found` fallback.
+    pub fn guard(mut self, guard: impl Guard) -> Self {
+        self.guards.push(Box::new(guard));
+        self
+    }
+}
+
+impl<B> Router<B>
+where
+    B: HttpBody + Send + Sync + 'static,
+{
+    /// Build a group of routes under a shared path prefix; see [`Scope`].
+    ///
+    /// ```ignore
+    /// Router::new().scope("/api", |scope| {
+    ///     scope
+    ///         .guard(Host("api.example.com".to_owned()))
+    ///         .route("/users", get(users))
+    ///         .fallback(api_not_found)
+    /// })
+    /// ```
+    pub fn scope(self, prefix: &str, f: impl FnOnce(Scope<B>) -> Scope<B>) -> Self {
+        let Scope {
+            mut router,
+            guards,
+            fallback,
+        } = f(Scope::new());
+
+        let scope_fallback = fallback.clone();
+        if let Some(fallback) = fallback {
+            router = router.route(SCOPE_FALLBACK_PATH, fallback);
+        }
+
+        let inner = if guards.is_empty() {
+            router
+        } else {
+            // Guard rejections go to the scope's own fallback when it has one.
+            let fallback = match scope_fallback {
+                Some(method_router) => tower::util::BoxCloneService::new(method_router),
+                None => match &router.fallback {
+                    Fallback::Default(svc) => tower::util::BoxCloneService::new(svc.clone()),
+                    Fallback::Custom(svc) => tower::util::BoxCloneService::new(svc.clone()),
+                },
+            };
+            router.layer(GuardLayer::new(guards, fallback))
+        };
+
+        self.nest(prefix, inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reloadable::ReloadableRouterService, routing::get, test_helpers::TestClient};
+    use http::{Request, StatusCode};
+    use hyper::Body;
+
+    #[tokio::test]
+    async fn scope_fallback_is_used_for_its_own_prefix_not_the_outer_default() {
+        let app: ReloadableRouterService = Router::new()
+            .fallback(get(|_: Request<Body>| async {
+                (StatusCode::NOT_FOUND, "outer fallback")
+            }))
+            .scope("/api", |scope| {
+                scope
+                    .route(
+                        "/users",
+                        get(|_: Request<Body>| async { "users#index" }),
+                    )
+                    .fallback(get(|_: Request<Body>| async {
+                        (StatusCode::IM_A_TEAPOT, "scope fallback")
+                    }))
+            })
+            .into();
+
+        let client = TestClient::new(app);
+
+        // Matches the prefix but no inner route: the scope's own fallback.
+        let res = client.get("/api/missing").send().await;
+        assert_eq!(res.status(), StatusCode::IM_A_TEAPOT);
+        assert_eq!(res.text().await, "scope fallback");
+
+        // Doesn't match the prefix at all: the outer router's fallback.
+        let res = client.get("/missing").send().await;
+        assert_eq!(res.status(), StatusCode::NOT_FOUND);
+        assert_eq!(res.text().await, "outer fallback");
+
+        // A route actually inside the scope still dispatches normally.
+        let res = client.get("/api/users").send().await;
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(res.text().await, "users#index");
+    }
+
+    #[tokio::test]
+    async fn scope_guard_rejection_falls_through_to_the_scope_fallback() {
+        use super::super::Host;
+
+        let app: ReloadableRouterService = Router::new()
+            .scope("/api", |scope| {
+                scope
+                    .guard(Host("api.example.com".to_owned()))
+                    .route(
+                        "/users",
+                        get(|_: Request<Body>| async { "users#index" }),
+                    )
+                    .fallback(get(|_: Request<Body>| async {
+                        (StatusCode::IM_A_TEAPOT, "scope fallback")
+                    }))
+            })
+            .into();
+
+        let client = TestClient::new(app);
+
+        let res = client
+            .get("/api/users")
+            .header(http::header::HOST, "other.com")
+            .send()
+            .await;
+        assert_eq!(res.status(), StatusCode::IM_A_TEAPOT);
+        assert_eq!(res.text().await, "scope fallback");
+    }
+}